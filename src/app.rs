@@ -1,5 +1,7 @@
 use log::{debug, trace};
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 use crate::errors::{Error, HurlResult};
@@ -46,10 +48,133 @@ pub struct App {
     #[structopt(short, long)]
     pub secure: bool,
 
-    /// The HTTP Method to use, one of: HEAD, GET, POST, PUT, PATCH, DELETE.
+    /// Expect the response status to equal this code.
+    ///
+    /// Enables check mode: if this (or any other `--expect-*` flag) does
+    /// not hold, `hurl` prints a diff of expected vs. actual and exits
+    /// non-zero, e.g. `hurl GET url --expect-status 200` as a health probe.
+    /// Cannot be combined with `--download`/`--output`, `--paginate`, or
+    /// `--stream`, since those modes don't produce a single checkable
+    /// response.
+    #[structopt(long)]
+    pub expect_status: Option<u16>,
+
+    /// Expect a response header, as `key` or `key:value`. May be given
+    /// multiple times. Enables check mode; see `--expect-status`.
+    #[structopt(long)]
+    pub expect_header: Vec<String>,
+
+    /// Expect a dotted path into the JSON body to equal a value, as
+    /// `path=value` (e.g. `data.items.0.id=42`). Only object keys and
+    /// numeric array indices are supported. May be given multiple times.
+    /// Enables check mode; see `--expect-status`.
+    #[structopt(long)]
+    pub expect_jsonpath: Vec<String>,
+
+    /// HTTP/HTTPS/SOCKS proxy URL to route every request through.
+    #[structopt(long)]
+    pub proxy: Option<String>,
+
+    /// Disable TLS certificate verification.
+    ///
+    /// Equivalent to curl's `-k`/`--insecure`. Useful for internal CAs or
+    /// local development; avoid it against untrusted networks.
+    #[structopt(long)]
+    pub insecure: bool,
+
+    /// Override the `User-Agent` header sent with every request.
+    #[structopt(long)]
+    pub user_agent: Option<String>,
+
+    /// Headers applied to every request, set via the config file.
+    ///
+    /// Overridden per-request by a matching `Parameter::Header` on the
+    /// command line.
+    #[structopt(skip)]
+    pub default_headers: HashMap<String, String>,
+
+    /// Automatically follow `Link: rel="next"` pagination headers.
+    ///
+    /// After printing a page, follows the response's `rel="next"` link
+    /// and reissues the same method, session, auth, and headers against
+    /// it, repeating until no `next` link remains or, if a count is
+    /// given (`--paginate 5`), until that many pages have been fetched.
+    /// Stops early if a `next` link points back at an already-visited URL.
+    #[structopt(long, min_values = 0, max_values = 1)]
+    pub paginate: Option<Option<u32>>,
+
+    /// Concatenate paginated pages into a single JSON array.
+    ///
+    /// When every page fetched via `--paginate` is a JSON array, their
+    /// contents are combined and pretty-printed once at the end instead
+    /// of printing each page separately. If a page turns out not to be a
+    /// JSON array, the pages merged so far are flushed and printing falls
+    /// back to one page at a time for the rest of the run. Has no effect
+    /// without `--paginate`.
+    #[structopt(long)]
+    pub merge: bool,
+
+    /// Follow a long-lived response as a stream of events instead of
+    /// buffering the whole body.
+    ///
+    /// Enabled automatically when the response `Content-Type` is
+    /// `text/event-stream`. Lines are accumulated until a blank line,
+    /// then printed as one event (`event:`/`data:`/`id:` fields parsed per
+    /// the Server-Sent Events format), repeating until EOF or Ctrl-C.
+    #[structopt(long)]
+    pub stream: bool,
+
+    /// Save the response body to a file instead of printing it.
+    ///
+    /// Implies `--download`; the body is streamed straight to disk rather
+    /// than buffered and JSON-pretty-printed.
+    #[structopt(short, long, parse(from_os_str))]
+    pub output: Option<PathBuf>,
+
+    /// Stream the response body to a file instead of printing it.
+    ///
+    /// Without `--output`, the filename is derived from the response's
+    /// `Content-Disposition` header, falling back to the last URL path
+    /// segment, then to `index.html`.
+    #[structopt(long)]
+    pub download: bool,
+
+    /// Per-request timeout, in seconds.
+    ///
+    /// If the server has not responded within this many seconds the
+    /// request is aborted (and retried, if `--retries` is set).
+    #[structopt(long)]
+    pub timeout: Option<u64>,
+
+    /// Number of times to retry a failed request.
+    ///
+    /// A request is retried on timeout, connection error, or a
+    /// 502/503/504 response, waiting `retry_backoff * 2^attempt`
+    /// (plus jitter) between attempts. If a timeout or connection error is
+    /// still happening once retries are exhausted, `hurl` exits with an
+    /// error. A 5xx response is always returned to the caller once retries
+    /// (if any) are exhausted, so it can still be printed or checked with
+    /// `--expect-status`. Defaults to no retries.
+    #[structopt(long)]
+    pub retries: Option<u32>,
+
+    /// Base delay, in milliseconds, for the retry backoff.
+    #[structopt(long)]
+    pub retry_backoff: Option<u64>,
+
+    /// The HTTP Method to use, one of: HEAD, GET, POST, PUT, PATCH, DELETE,
+    /// OPTIONS, TRACE, CONNECT.
     #[structopt(subcommand)]
     pub cmd: Option<Method>,
 
+    /// Use a custom HTTP method.
+    ///
+    /// Sends a verb that isn't covered by the built-in subcommands, e.g.
+    /// `hurl --method PROPFIND https://example.com`. Takes precedence over
+    /// any method subcommand or the inferred GET/POST.
+    #[structopt(short = "X", long)]
+    pub method: Option<String>,
+
     /// The URL to issue a request to if a method subcommand is not specified.
     pub url: Option<String>,
 
@@ -95,9 +220,26 @@ impl App {
         if self.cmd.is_none() && self.url.is_none() {
             return Err(Error::MissingUrlAndCommand);
         }
+        if self.has_checks() {
+            if self.download || self.output.is_some() {
+                return Err(Error::ChecksUnsupportedWithMode("--download/--output"));
+            }
+            if self.paginate.is_some() {
+                return Err(Error::ChecksUnsupportedWithMode("--paginate"));
+            }
+            if self.stream {
+                return Err(Error::ChecksUnsupportedWithMode("--stream"));
+            }
+        }
         Ok(())
     }
 
+    pub fn has_checks(&self) -> bool {
+        self.expect_status.is_some()
+            || !self.expect_header.is_empty()
+            || !self.expect_jsonpath.is_empty()
+    }
+
     pub fn log_level(&self) -> Option<&'static str> {
         if self.quiet || self.verbose <= 0 {
             return None;
@@ -122,6 +264,9 @@ pub enum Method {
     POST(MethodData),
     PATCH(MethodData),
     DELETE(MethodData),
+    OPTIONS(MethodData),
+    TRACE(MethodData),
+    CONNECT(MethodData),
 }
 
 impl Method {
@@ -134,6 +279,9 @@ impl Method {
             POST(x) => x,
             PATCH(x) => x,
             DELETE(x) => x,
+            OPTIONS(x) => x,
+            TRACE(x) => x,
+            CONNECT(x) => x,
         }
     }
 }
@@ -179,7 +327,7 @@ pub struct MethodData {
     pub parameters: Vec<Parameter>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Parameter {
     // :
     Header { key: String, value: String },
@@ -197,6 +345,12 @@ pub enum Parameter {
     RawJsonDataFile { key: String, filename: String },
 }
 
+impl Parameter {
+    pub fn is_header(&self) -> bool {
+        matches!(self, Parameter::Header { .. })
+    }
+}
+
 #[derive(Debug)]
 enum Token<'a> {
     Text(&'a str),
@@ -4,10 +4,43 @@ use log::{info, debug, trace, log_enabled, self};
 use reqwest::multipart::Form;
 use reqwest::{Client, RequestBuilder, Response, Url};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::BufReader;
-use std::time::Instant;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Ceiling on the exponential backoff delay between retries.
+const MAX_RETRY_BACKOFF_MS: u64 = 30_000;
+
+fn retry_delay(base_ms: u64, attempt: u32) -> Duration {
+    let exp = base_ms.saturating_mul(1u64 << attempt.min(16));
+    let capped = exp.min(MAX_RETRY_BACKOFF_MS);
+    let jitter_cap = (capped / 10).max(1);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let jitter = u64::from(nanos) % jitter_cap;
+    Duration::from_millis(capped + jitter)
+}
+
+impl From<&Method> for reqwest::Method {
+    fn from(method: &Method) -> Self {
+        use Method::*;
+        match method {
+            HEAD(_) => reqwest::Method::HEAD,
+            GET(_) => reqwest::Method::GET,
+            PUT(_) => reqwest::Method::PUT,
+            POST(_) => reqwest::Method::POST,
+            PATCH(_) => reqwest::Method::PATCH,
+            DELETE(_) => reqwest::Method::DELETE,
+            OPTIONS(_) => reqwest::Method::OPTIONS,
+            TRACE(_) => reqwest::Method::TRACE,
+            CONNECT(_) => reqwest::Method::CONNECT,
+        }
+    }
+}
 
 pub fn perform_method(
     app: &App,
@@ -31,7 +64,20 @@ pub fn perform(
     raw_url: &str,
     parameters: &Vec<Parameter>,
 ) -> HurlResult<Response> {
-    let client = Client::new();
+    let mut client_builder = Client::builder();
+    if let Some(timeout) = app.timeout {
+        client_builder = client_builder.timeout(Duration::from_secs(timeout));
+    }
+    if let Some(ref proxy_url) = app.proxy {
+        client_builder = client_builder.proxy(reqwest::Proxy::all(proxy_url)?);
+    }
+    if app.insecure {
+        client_builder = client_builder.danger_accept_invalid_certs(true);
+    }
+    if let Some(ref user_agent) = app.user_agent {
+        client_builder = client_builder.user_agent(user_agent);
+    }
+    let client = client_builder.build().map_err(|_| Error::ClientOther)?;
     let url = parse(app, raw_url)?;
     debug!("Parsed url: {}", url);
 
@@ -43,25 +89,113 @@ pub fn perform(
         }
     }
 
-    let mut builder = client.request(method, url);
-    builder = handle_session(
-        builder,
-        session,
-        parameters,
-        !app.read_only,
-        &app.auth,
-        &app.token,
-    );
-    builder = handle_parameters(builder, app.form, is_multipart, parameters)?;
-    builder = handle_auth(builder, &app.auth, &app.token)?;
+    let user_header_keys: HashSet<String> = parameters
+        .iter()
+        .filter_map(|p| match p {
+            Parameter::Header { key, .. } => Some(key.to_lowercase()),
+            _ => None,
+        })
+        .collect();
+
+    let retries = app.retries.unwrap_or(0);
+    let retry_backoff = app.retry_backoff.unwrap_or(500);
+
+    let mut attempt = 0;
+    loop {
+        let mut builder = client.request(method.clone(), url.clone());
+        for (key, value) in &app.default_headers {
+            if !user_header_keys.contains(&key.to_lowercase()) {
+                builder = builder.header(key.as_str(), value.as_str());
+            }
+        }
+        builder = handle_session(
+            builder,
+            session,
+            parameters,
+            !app.read_only,
+            &app.auth,
+            &app.token,
+        );
+        builder = handle_parameters(builder, app.form, is_multipart, parameters)?;
+        builder = handle_auth(builder, &app.auth, &app.token)?;
+
+        let send_result = if log_enabled!(log::Level::Info) {
+            let start = Instant::now();
+            let result = builder.send();
+            let elapsed = start.elapsed();
+            info!("Elapsed time: {:?}", elapsed);
+            result
+        } else {
+            builder.send()
+        };
+
+        match send_result {
+            Ok(resp) => {
+                let status = resp.status();
+                let is_retryable_status = matches!(status.as_u16(), 502 | 503 | 504);
+                if is_retryable_status && attempt < retries {
+                    debug!(
+                        "Got status {}, retrying (attempt {} of {})",
+                        status,
+                        attempt + 1,
+                        retries
+                    );
+                    thread::sleep(retry_delay(retry_backoff, attempt));
+                    attempt += 1;
+                    continue;
+                }
+                // A 5xx is still a well-formed response, so it's returned
+                // as-is (rather than as an error) whether or not retries
+                // were exhausted, letting the caller print it or run
+                // `--expect-status` against it.
+                return Ok(resp);
+            }
+            Err(e) => {
+                let is_retryable_error = e.is_timeout() || e.is_connect();
+                if is_retryable_error && attempt < retries {
+                    debug!(
+                        "Request error ({}), retrying (attempt {} of {})",
+                        e,
+                        attempt + 1,
+                        retries
+                    );
+                    thread::sleep(retry_delay(retry_backoff, attempt));
+                    attempt += 1;
+                    continue;
+                }
+                // Unlike a 5xx, there's no response to hand back here, so
+                // a timeout or connection error that's still happening
+                // once retries are exhausted is a hard failure.
+                return Err(if e.is_timeout() {
+                    Error::ClientTimeout
+                } else if e.is_connect() {
+                    Error::ClientOther
+                } else {
+                    Error::from(e)
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_each_attempt() {
+        assert!(retry_delay(100, 0).as_millis() >= 100);
+        assert!(retry_delay(100, 0).as_millis() < 110);
+        assert!(retry_delay(100, 1).as_millis() >= 200);
+        assert!(retry_delay(100, 1).as_millis() < 220);
+        assert!(retry_delay(100, 2).as_millis() >= 400);
+        assert!(retry_delay(100, 2).as_millis() < 440);
+    }
 
-    if log_enabled!(log::Level::Info) {
-        let start = Instant::now();
-        let result = builder.send().map_err(From::from);
-        let elapsed = start.elapsed();
-        info!("Elapsed time: {:?}", elapsed);
-        result
-    } else {
-        builder.send().map_err(From::from)
+    #[test]
+    fn retry_delay_is_capped() {
+        let capped = retry_delay(100, 20).as_millis() as u64;
+        assert!(capped >= MAX_RETRY_BACKOFF_MS);
+        assert!(capped < MAX_RETRY_BACKOFF_MS + MAX_RETRY_BACKOFF_MS / 10 + 1);
     }
 }
@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -12,4 +13,85 @@ pub struct Config {
     pub auth: Option<String>,
     pub token: Option<String>,
     pub secure: Option<bool>,
+    pub timeout: Option<u64>,
+    pub retries: Option<u32>,
+    pub retry_backoff: Option<u64>,
+    pub proxy: Option<String>,
+    pub insecure: Option<bool>,
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub default_headers: HashMap<String, String>,
+}
+
+impl Config {
+    fn path() -> PathBuf {
+        DIRECTORIES.config_dir().join("config.json")
+    }
+
+    fn load() -> Option<Config> {
+        let contents = fs::read_to_string(Self::path()).ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                eprintln!("Failed to parse config file: {}", e);
+                None
+            }
+        }
+    }
+}
+
+impl App {
+    /// Fills in any setting the user didn't pass on the command line from
+    /// the config file, if one exists. Command line flags always win.
+    pub fn process_config_file(&mut self) {
+        let config = match Config::load() {
+            Some(config) => config,
+            None => return,
+        };
+
+        if self.verbose == 0 {
+            if let Some(verbose) = config.verbose {
+                self.verbose = verbose;
+            }
+        }
+        if !self.form {
+            if let Some(form) = config.form {
+                self.form = form;
+            }
+        }
+        if self.auth.is_none() {
+            self.auth = config.auth;
+        }
+        if self.token.is_none() {
+            self.token = config.token;
+        }
+        if !self.secure {
+            if let Some(secure) = config.secure {
+                self.secure = secure;
+            }
+        }
+        if self.timeout.is_none() {
+            self.timeout = config.timeout;
+        }
+        if self.retries.is_none() {
+            self.retries = config.retries;
+        }
+        if self.retry_backoff.is_none() {
+            self.retry_backoff = config.retry_backoff;
+        }
+        if self.proxy.is_none() {
+            self.proxy = config.proxy;
+        }
+        if !self.insecure {
+            if let Some(insecure) = config.insecure {
+                self.insecure = insecure;
+            }
+        }
+        if self.user_agent.is_none() {
+            self.user_agent = config.user_agent;
+        }
+        for (key, value) in config.default_headers {
+            self.default_headers.entry(key).or_insert(value);
+        }
+    }
 }
@@ -4,6 +4,9 @@ pub enum Error {
     ParameterMissingSeparator(String),
     MissingUrlAndCommand,
     NotFormButHasFormFile,
+    InvalidMethod(String),
+    AssertionFailed(String),
+    ChecksUnsupportedWithMode(&'static str),
     ClientSerialization,
     ClientTimeout,
     ClientWithStatus(reqwest::StatusCode),
@@ -23,6 +26,13 @@ impl fmt::Display for Error {
                 write!(f, "Missing separator when parsing parameter: {}", s)
             }
             Error::MissingUrlAndCommand => write!(f, "Must specify a url or a command!"),
+            Error::InvalidMethod(m) => write!(f, "Not a valid HTTP method: {}", m),
+            Error::AssertionFailed(msg) => write!(f, "Check failed: {}", msg),
+            Error::ChecksUnsupportedWithMode(mode) => write!(
+                f,
+                "--expect-status/--expect-header/--expect-jsonpath cannot be combined with {}",
+                mode
+            ),
             Error::NotFormButHasFormFile => write!(
                 f,
                 "Cannot have a form file 'key@filename' unless --form option is set"
@@ -1,6 +1,10 @@
 use structopt::StructOpt;
 use heck::TitleCase;
 use log::trace;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
 
 mod app;
 mod client;
@@ -9,7 +13,7 @@ mod directories;
 mod errors;
 mod session;
 
-use errors::HurlResult;
+use errors::{Error, HurlResult};
 
 use syntect::highlighting::Theme;
 use syntect::parsing::SyntaxSet;
@@ -36,10 +40,33 @@ fn main() -> HurlResult<()> {
         .as_ref()
         .map(|name| session::Session::get_or_create(&app, name.clone(), app.host()));
 
+    if let Some(name) = app.method.clone() {
+        let method = reqwest::Method::from_bytes(name.as_bytes())
+            .map_err(|_| Error::InvalidMethod(name))?;
+        let (url, parameters) = match app.cmd {
+            Some(ref cmd) => {
+                let data = cmd.data();
+                (data.url.clone(), data.parameters.clone())
+            }
+            None => (app.url.take().unwrap(), app.parameters.clone()),
+        };
+        let resp = client::perform(&app, method.clone(), &mut session, &url, &parameters)?;
+        return dispatch_response(&app, &ss, theme, method, &parameters, resp, &mut session);
+    }
+
     match app.cmd {
         Some(ref method) => {
             let resp = client::perform_method(&app, method, &mut session)?;
-            handle_response(&app, &ss, theme, resp, &mut session)
+            let data = method.data();
+            dispatch_response(
+                &app,
+                &ss,
+                theme,
+                method.into(),
+                &data.parameters,
+                resp,
+                &mut session,
+            )
         }
         None => {
             let url = app.url.take().unwrap();
@@ -49,12 +76,45 @@ fn main() -> HurlResult<()> {
             } else {
                 reqwest::Method::GET
             };
-            let resp = client::perform(&app, method, &mut session, &url, &app.parameters)?;
-            handle_response(&app, &ss, theme, resp, &mut session)
+            let resp = client::perform(&app, method.clone(), &mut session, &url, &app.parameters)?;
+            dispatch_response(&app, &ss, theme, method, &app.parameters, resp, &mut session)
         }
     }
 }
 
+/// Routes a response to the pagination or streaming handler, falling back
+/// to the normal print-and-exit handler.
+fn dispatch_response(
+    app: &app::App,
+    ss: &SyntaxSet,
+    theme: &Theme,
+    method: reqwest::Method,
+    parameters: &[app::Parameter],
+    resp: reqwest::Response,
+    session: &mut Option<session::Session>,
+) -> HurlResult<()> {
+    if app.paginate.is_some() && !(app.download || app.output.is_some()) {
+        return handle_paginated(app, ss, theme, method, parameters, resp, session);
+    }
+
+    let is_event_stream = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.starts_with("text/event-stream"))
+        .unwrap_or(false);
+
+    // `--stream`/`--download`/`--paginate` combined with `--expect-*` are
+    // rejected in `App::validate`, but an auto-detected `text/event-stream`
+    // response can't be known ahead of time; route it through the normal
+    // handler instead so the checks still run.
+    if (app.stream || is_event_stream) && !app.has_checks() {
+        handle_stream(ss, theme, resp)
+    } else {
+        handle_response(app, ss, theme, resp, session)
+    }
+}
+
 fn handle_response(
     app: &app::App,
     ss: &SyntaxSet,
@@ -78,6 +138,14 @@ fn handle_response(
             value.to_str().unwrap_or("BAD HEADER VALUE")
         ));
     }
+    if app.download || app.output.is_some() {
+        headers.sort();
+        s.push_str(&(&headers[..]).join("\n"));
+        highlight_string(ss, theme, "HTTP", &s);
+        println!("");
+        return download_response(app, &mut resp, session);
+    }
+
     let result = resp.text()?;
     let content_length = match resp.content_length() {
         Some(len) => len,
@@ -107,5 +175,536 @@ fn handle_response(
             s.save(app)?;
         }
     }
+
+    if app.has_checks() {
+        run_checks(app, status, &resp, &result)?;
+    }
     Ok(())
 }
+
+/// Evaluates `--expect-*` assertions against a response, printing a
+/// human-readable diff and returning `Error::AssertionFailed` if any fail.
+fn run_checks(
+    app: &app::App,
+    status: reqwest::StatusCode,
+    resp: &reqwest::Response,
+    body: &str,
+) -> HurlResult<()> {
+    let mut failures = Vec::new();
+
+    if let Some(expected) = app.expect_status {
+        if status.as_u16() != expected {
+            failures.push(format!(
+                "status: expected {}, got {}",
+                expected,
+                status.as_u16()
+            ));
+        }
+    }
+
+    for raw in &app.expect_header {
+        let mut parts = raw.splitn(2, ':');
+        let key = parts.next().unwrap_or("").trim();
+        let expected_value = parts.next().map(str::trim);
+        match resp.headers().get(key) {
+            None => failures.push(format!("header {}: expected, but missing", key)),
+            Some(actual) => {
+                if let Some(expected_value) = expected_value {
+                    let actual_value = actual.to_str().unwrap_or("");
+                    if actual_value != expected_value {
+                        failures.push(format!(
+                            "header {}: expected \"{}\", got \"{}\"",
+                            key, expected_value, actual_value
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    if !app.expect_jsonpath.is_empty() {
+        let body_json: Option<serde_json::Value> = serde_json::from_str(body).ok();
+        for raw in &app.expect_jsonpath {
+            let mut parts = raw.splitn(2, '=');
+            let path = parts.next().unwrap_or("");
+            let expected_value = parts.next().unwrap_or("");
+            let actual = body_json.as_ref().and_then(|v| json_path_lookup(v, path));
+            match actual {
+                Some(value) => {
+                    let actual_value = json_value_to_compare_string(value);
+                    if actual_value != expected_value {
+                        failures.push(format!(
+                            "jsonpath {}: expected \"{}\", got \"{}\"",
+                            path, expected_value, actual_value
+                        ));
+                    }
+                }
+                None => failures.push(format!("jsonpath {}: path not found in body", path)),
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        eprintln!("\u{2713} all checks passed");
+        Ok(())
+    } else {
+        for failure in &failures {
+            eprintln!("\u{2717} {}", failure);
+        }
+        Err(Error::AssertionFailed(failures.join("; ")))
+    }
+}
+
+/// Looks up a dotted path (object keys and numeric array indices) into a
+/// JSON value, e.g. `data.items.0.id`.
+fn json_path_lookup<'a>(value: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    let mut current = value;
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        current = match current {
+            serde_json::Value::Object(map) => map.get(segment)?,
+            serde_json::Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+fn json_value_to_compare_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Streams a response body to disk, for `--download`/`--output`.
+fn download_response(
+    app: &app::App,
+    resp: &mut reqwest::Response,
+    session: &mut Option<session::Session>,
+) -> HurlResult<()> {
+    let path = download_path(resp, app.output.as_ref());
+    let file = File::create(&path)?;
+    let mut writer = BufWriter::new(file);
+
+    let mut buf = [0u8; 8192];
+    let mut total: u64 = 0;
+    loop {
+        let n = resp.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buf[..n])?;
+        total += n as u64;
+    }
+    writer.flush()?;
+
+    eprintln!("Saved {} bytes to {}", total, path.display());
+
+    if !app.read_only {
+        if let Some(s) = session {
+            s.update_with_response(resp);
+            s.save(app)?;
+        }
+    }
+    Ok(())
+}
+
+/// Picks the file to save a downloaded response to: the explicit
+/// `--output` path if given, otherwise the `Content-Disposition` filename,
+/// falling back to the last URL path segment, then to `index.html`.
+fn download_path(resp: &reqwest::Response, explicit: Option<&PathBuf>) -> PathBuf {
+    if let Some(path) = explicit {
+        return path.clone();
+    }
+    if let Some(name) = resp
+        .headers()
+        .get(reqwest::header::CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(content_disposition_filename)
+    {
+        return PathBuf::from(name);
+    }
+    let from_url = resp
+        .url()
+        .path_segments()
+        .and_then(|mut segs| segs.next_back())
+        .filter(|s| !s.is_empty());
+    PathBuf::from(from_url.unwrap_or("index.html"))
+}
+
+/// Extracts the `filename` parameter from a `Content-Disposition` header,
+/// reduced to its final path component so a hostile or buggy server can't
+/// use `../` or an absolute path to write outside the working directory.
+fn content_disposition_filename(value: &str) -> Option<String> {
+    value.split(';').map(str::trim).find_map(|part| {
+        let rest = part.strip_prefix("filename=")?;
+        let name = rest.trim_matches('"');
+        let base = Path::new(name).file_name()?.to_str()?;
+        if base.is_empty() || base == "." || base == ".." {
+            return None;
+        }
+        Some(base.to_string())
+    })
+}
+
+/// Follows `Link: rel="next"` headers, printing (or merging) each page.
+fn handle_paginated(
+    app: &app::App,
+    ss: &SyntaxSet,
+    theme: &Theme,
+    method: reqwest::Method,
+    parameters: &[app::Parameter],
+    mut resp: reqwest::Response,
+    session: &mut Option<session::Session>,
+) -> HurlResult<()> {
+    let header_params: Vec<app::Parameter> =
+        parameters.iter().filter(|p| p.is_header()).cloned().collect();
+    let limit = app.paginate.unwrap_or(None);
+
+    let mut merged: Vec<serde_json::Value> = Vec::new();
+    let mut merge_ok = app.merge;
+    let mut page = 0u32;
+    let mut visited_urls: HashSet<String> = HashSet::new();
+    visited_urls.insert(resp.url().to_string());
+    loop {
+        let next_url = link_header(&resp, "next");
+
+        // While merging, stdout must stay valid JSON for `--paginate --merge
+        // | jq`, so the page banner is only printed once we've fallen back
+        // to per-page output.
+        if !merge_ok {
+            highlight_string(ss, theme, "HTTP", &page_banner(page, &resp));
+        }
+        let body = resp.text()?;
+        let value: serde_json::Result<serde_json::Value> = serde_json::from_str(&body);
+
+        if merge_ok {
+            match value {
+                Ok(serde_json::Value::Array(items)) => merged.extend(items),
+                _ => {
+                    trace!(
+                        "Page {} is not a JSON array; printing the {} item(s) merged so far and \
+                         falling back to per-page output",
+                        page + 1,
+                        merged.len()
+                    );
+                    if !merged.is_empty() {
+                        let flushed = serde_json::Value::Array(std::mem::take(&mut merged));
+                        print_page(&Ok(flushed), "");
+                    }
+                    merge_ok = false;
+                    highlight_string(ss, theme, "HTTP", &page_banner(page, &resp));
+                    print_page(&value, &body);
+                }
+            }
+        } else {
+            print_page(&value, &body);
+        }
+
+        if !app.read_only {
+            if let Some(s) = session {
+                s.update_with_response(&resp);
+                s.save(app)?;
+            }
+        }
+
+        page += 1;
+        if let Some(limit) = limit {
+            if page >= limit {
+                break;
+            }
+        }
+        let next_url = match next_url {
+            Some(url) => url,
+            None => break,
+        };
+        if !visited_urls.insert(next_url.clone()) {
+            trace!(
+                "Link: rel=\"next\" pointed back at an already-visited URL ({}); stopping to \
+                 avoid an infinite loop",
+                next_url
+            );
+            break;
+        }
+        resp = client::perform(app, method.clone(), session, &next_url, &header_params)?;
+    }
+
+    if merge_ok && !merged.is_empty() {
+        print_page(&Ok(serde_json::Value::Array(merged)), "");
+    }
+    Ok(())
+}
+
+/// Builds the `--- page N: <status line> ---` banner printed ahead of each
+/// page when pages are shown individually (i.e. not merged).
+fn page_banner(page: u32, resp: &reqwest::Response) -> String {
+    let status = resp.status();
+    format!(
+        "--- page {}: {:?} {} {} ---\n",
+        page + 1,
+        resp.version(),
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("Unknown")
+    )
+}
+
+fn print_page(value: &serde_json::Result<serde_json::Value>, raw: &str) {
+    match value {
+        Ok(v) => {
+            let pretty = serde_json::to_string_pretty(v).unwrap_or_else(|_| raw.to_string());
+            println!("{}", pretty);
+        }
+        Err(_) => println!("{}", raw),
+    }
+}
+
+/// Finds the URL of the `rel="next"` (or other) link in a `Link` header.
+fn link_header(resp: &reqwest::Response, rel: &str) -> Option<String> {
+    let raw = resp.headers().get(reqwest::header::LINK)?.to_str().ok()?;
+    parse_link_header(raw, rel)
+}
+
+/// Parses an RFC 8288 `Link` header, handling multiple comma-separated
+/// links and quoted `rel` parameters.
+fn parse_link_header(raw: &str, rel: &str) -> Option<String> {
+    for link in split_link_header(raw) {
+        let mut parts = link.splitn(2, ';');
+        let url_part = parts.next()?.trim();
+        if !(url_part.starts_with('<') && url_part.ends_with('>')) {
+            continue;
+        }
+        let url = &url_part[1..url_part.len() - 1];
+        for param in parts.next().unwrap_or("").split(';') {
+            let param = param.trim();
+            if let Some(value) = param.strip_prefix("rel=") {
+                let matches_rel = value.trim_matches('"').split_whitespace().any(|r| r == rel);
+                if matches_rel {
+                    return Some(url.to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Splits a `Link` header on the commas that separate entries, ignoring
+/// commas inside quoted parameter values.
+fn split_link_header(raw: &str) -> Vec<&str> {
+    let mut entries = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    for (i, c) in raw.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                entries.push(raw[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    entries.push(raw[start..].trim());
+    entries
+}
+
+/// One accumulated Server-Sent Event, per the `text/event-stream` format.
+#[derive(Debug, Default)]
+struct SseEvent {
+    id: Option<String>,
+    event: Option<String>,
+    data: String,
+}
+
+impl SseEvent {
+    fn is_empty(&self) -> bool {
+        self.id.is_none() && self.event.is_none() && self.data.is_empty()
+    }
+}
+
+/// Strips exactly one leading space from an SSE field's value, per spec,
+/// rather than trimming all surrounding whitespace.
+fn sse_field_value(rest: &str) -> &str {
+    rest.strip_prefix(' ').unwrap_or(rest)
+}
+
+/// Reads a long-lived response line by line, printing one highlighted
+/// block per Server-Sent Event as it arrives, until EOF or Ctrl-C.
+fn handle_stream(ss: &SyntaxSet, theme: &Theme, resp: reqwest::Response) -> HurlResult<()> {
+    let status = resp.status();
+    eprintln!(
+        "{:?} {} {}",
+        resp.version(),
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("Unknown")
+    );
+
+    let mut event = SseEvent::default();
+    for line in BufReader::new(resp).lines() {
+        let line = line?;
+        if line.is_empty() {
+            if !event.is_empty() {
+                print_sse_event(ss, theme, &event);
+            }
+            event = SseEvent::default();
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("event:") {
+            event.event = Some(sse_field_value(rest).to_string());
+        } else if let Some(rest) = line.strip_prefix("data:") {
+            if !event.data.is_empty() {
+                event.data.push('\n');
+            }
+            event.data.push_str(sse_field_value(rest));
+        } else if let Some(rest) = line.strip_prefix("id:") {
+            event.id = Some(sse_field_value(rest).to_string());
+        }
+    }
+    if !event.is_empty() {
+        print_sse_event(ss, theme, &event);
+    }
+    Ok(())
+}
+
+fn print_sse_event(ss: &SyntaxSet, theme: &Theme, event: &SseEvent) {
+    let mut header = String::new();
+    if let Some(ref id) = event.id {
+        header.push_str(&format!("id: {}\n", id));
+    }
+    if let Some(ref name) = event.event {
+        header.push_str(&format!("event: {}\n", name));
+    }
+    if !header.is_empty() {
+        highlight_string(ss, theme, "HTTP", &header);
+    }
+
+    let result_json: serde_json::Result<OrderedJson> = serde_json::from_str(&event.data);
+    match result_json {
+        Ok(value) => {
+            let pretty =
+                serde_json::to_string_pretty(&value).unwrap_or_else(|_| event.data.clone());
+            println!("{}", pretty);
+        }
+        Err(_) => println!("{}", event.data),
+    }
+    println!("");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_disposition_filename_plain() {
+        assert_eq!(
+            content_disposition_filename(r#"attachment; filename="report.pdf""#),
+            Some("report.pdf".to_string())
+        );
+    }
+
+    #[test]
+    fn content_disposition_filename_rejects_path_traversal() {
+        assert_eq!(
+            content_disposition_filename(r#"attachment; filename="../../../etc/cron.d/x""#),
+            Some("x".to_string())
+        );
+    }
+
+    #[test]
+    fn content_disposition_filename_rejects_absolute_path() {
+        assert_eq!(
+            content_disposition_filename(r#"attachment; filename="/etc/passwd""#),
+            Some("passwd".to_string())
+        );
+    }
+
+    #[test]
+    fn content_disposition_filename_rejects_empty_and_dots() {
+        assert_eq!(content_disposition_filename(r#"attachment; filename="""#), None);
+        assert_eq!(content_disposition_filename(r#"attachment; filename=".""#), None);
+        assert_eq!(content_disposition_filename(r#"attachment; filename="..""#), None);
+    }
+
+    #[test]
+    fn content_disposition_filename_missing() {
+        assert_eq!(content_disposition_filename("inline"), None);
+    }
+
+    #[test]
+    fn parse_link_header_finds_rel_next() {
+        let raw = r#"<https://example.com/page=2>; rel="next", <https://example.com/page=1>; rel="prev""#;
+        assert_eq!(
+            parse_link_header(raw, "next"),
+            Some("https://example.com/page=2".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_link_header_unquoted_rel() {
+        let raw = "<https://example.com/page=2>; rel=next";
+        assert_eq!(
+            parse_link_header(raw, "next"),
+            Some("https://example.com/page=2".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_link_header_missing_rel() {
+        let raw = r#"<https://example.com/page=1>; rel="prev""#;
+        assert_eq!(parse_link_header(raw, "next"), None);
+    }
+
+    #[test]
+    fn split_link_header_ignores_commas_in_quotes() {
+        let raw = r#"<url1>; rel="next, weird", <url2>; rel="prev""#;
+        assert_eq!(
+            split_link_header(raw),
+            vec![r#"<url1>; rel="next, weird""#, r#"<url2>; rel="prev""#]
+        );
+    }
+
+    #[test]
+    fn split_link_header_single_entry() {
+        assert_eq!(split_link_header("<url1>; rel=\"next\""), vec!["<url1>; rel=\"next\""]);
+    }
+
+    #[test]
+    fn json_path_lookup_object_key() {
+        let value: serde_json::Value = serde_json::json!({"data": {"id": 42}});
+        assert_eq!(
+            json_path_lookup(&value, "data.id"),
+            Some(&serde_json::json!(42))
+        );
+    }
+
+    #[test]
+    fn json_path_lookup_array_index() {
+        let value: serde_json::Value = serde_json::json!({"items": [{"id": 1}, {"id": 2}]});
+        assert_eq!(
+            json_path_lookup(&value, "items.1.id"),
+            Some(&serde_json::json!(2))
+        );
+    }
+
+    #[test]
+    fn json_path_lookup_missing_path() {
+        let value: serde_json::Value = serde_json::json!({"data": {"id": 42}});
+        assert_eq!(json_path_lookup(&value, "data.missing"), None);
+    }
+
+    #[test]
+    fn json_path_lookup_out_of_bounds_index() {
+        let value: serde_json::Value = serde_json::json!({"items": [1, 2]});
+        assert_eq!(json_path_lookup(&value, "items.5"), None);
+    }
+
+    #[test]
+    fn sse_field_value_strips_single_leading_space() {
+        assert_eq!(sse_field_value(" hello"), "hello");
+    }
+
+    #[test]
+    fn sse_field_value_preserves_further_whitespace() {
+        assert_eq!(sse_field_value("  hello  "), " hello  ");
+        assert_eq!(sse_field_value("hello"), "hello");
+    }
+}